@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  pad2jsonl.rs - Export Agilent PAD captures as newline-delimited JSON.
+ *  Copyright (C) 2025  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use clap::Parser;
+
+use agilent_pad::*;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The PAD file to read.
+    pad_file: String,
+
+    /// Only emit records matching this filter expression, e.g.
+    /// `dir == us and tlp == mem_wr`.
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Undo the PCIe data scrambler before decoding each record, using
+    /// its captured `lfsr` seed.
+    #[arg(long)]
+    descramble: bool,
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let filter = match args.filter.as_deref().map(filter::Expr::parse) {
+        Some(Ok(expr)) => Some(expr),
+        Some(Err(error)) => {
+            eprintln!("Error parsing filter expression: {}", error);
+            return;
+        }
+        None => None,
+    };
+
+    let mut pad_file = match PadFile::from_filename(&args.pad_file) {
+        Ok(pf) => pf,
+        Err(error) => {
+            eprintln!("Error opening file {:?}: {:?}", &args.pad_file, error);
+            return;
+        }
+    };
+
+    let header = &pad_file.header;
+    println!(
+        "{{\"type\":\"trace\",\"module_type\":\"{}\",\"port_id\":\"{}\",\"guid\":\"{}\",\"channel_names\":{{\"a\":\"{}\",\"b\":\"{}\"}},\"trigger_record_number\":{},\"first_record_number\":{},\"last_record_number\":{},\"timestamps_ns\":{{\"first\":{},\"last\":{},\"stop\":{},\"trigger\":{}}}}}",
+        json_escape(&header.module_type),
+        json_escape(&header.port_id),
+        json_escape(&header.guid),
+        json_escape(&header.channel_names.a),
+        json_escape(&header.channel_names.b),
+        header.trigger_record_number,
+        header.first_record_number,
+        header.last_record_number,
+        header.timestamps_ns.first,
+        header.timestamps_ns.last,
+        header.timestamps_ns.stop,
+        header.timestamps_ns.trigger,
+    );
+
+    for record in &mut pad_file.records {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                eprintln!("Error reading record: {}", error);
+                break;
+            }
+        };
+
+        let data = match pad_file.records.data_for(&record) {
+            Ok(data) => data,
+            Err(error) => {
+                eprintln!("Record {}: error reading data: {}", record.number, error);
+                continue;
+            }
+        };
+        let payload_end = if record.data_valid {
+            (record.data_valid_count as usize).min(data.len())
+        } else {
+            data.len()
+        };
+
+        let metadata: Vec<String> = record
+            .metadata_tlvs(&data)
+            .map(|tlv| {
+                format!(
+                    "{{\"tag\":{},\"value\":\"{}\"}}",
+                    tlv.tag,
+                    hex_encode(tlv.value)
+                )
+            })
+            .collect();
+
+        let decoded_data = if args.descramble {
+            scramble::descramble(&data[..payload_end], record.lfsr)
+        } else {
+            data[..payload_end].to_vec()
+        };
+
+        if let Some(expr) = &filter {
+            let ctx = filter::Context {
+                header: &pad_file.header,
+                record: &record,
+                data: Some(&decoded_data),
+            };
+            if !expr.matches(&ctx) {
+                continue;
+            }
+        }
+
+        let decoded_tlps: Vec<String> = decode::decode(&decoded_data)
+            .iter()
+            .map(|packet| format!("\"{}\"", json_escape(&packet.to_string())))
+            .collect();
+
+        println!(
+            "{{\"type\":\"event\",\"number\":{},\"timestamp_ns\":{},\"count\":{},\"lfsr\":{},\"data_valid\":{},\"data_valid_count\":{},\"flags\":{},\"data\":\"{}\",\"metadata\":[{}],\"tlps\":[{}]}}",
+            record.number,
+            record.timestamp_ns,
+            record.count,
+            record.lfsr,
+            record.data_valid,
+            record.data_valid_count,
+            record.flags,
+            hex_encode(&decoded_data),
+            metadata.join(","),
+            decoded_tlps.join(","),
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 /*
  *  pad2pcapng.rs - Convert Agilent PAD files to PCAP-NG.
- *  Copyright (C) 2023-2024  Forest Crossman <cyrozap@gmail.com>
+ *  Copyright (C) 2023-2025  Forest Crossman <cyrozap@gmail.com>
  *
  *  This program is free software: you can redistribute it and/or modify
  *  it under the terms of the GNU General Public License as published by
@@ -19,11 +19,13 @@
  */
 
 use std::fs::File;
-use std::io::prelude::*;
 use std::io::BufWriter;
 
 use clap::Parser;
 
+use agilent_pad::pcapng::{
+    EnhancedPacketBlock, InterfaceDescriptionBlock, SectionHeaderBlock, WritableBlock,
+};
 use agilent_pad::*;
 
 #[derive(Parser, Debug)]
@@ -34,11 +36,52 @@ struct Args {
 
     /// The pcapng file to write.
     pcapng_file: String,
+
+    /// Only write records matching this filter expression, e.g.
+    /// `dir == us and tlp == mem_wr`.
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Undo the PCIe data scrambler before writing each record, using
+    /// its captured `lfsr` seed.
+    #[arg(long)]
+    descramble: bool,
+
+    /// Run each record's captured bytes through the descrambler from its
+    /// `lfsr` seed and report any record whose ending LFSR state doesn't
+    /// match the next record's seed. Implies `--descramble`.
+    #[arg(long)]
+    check_descramble: bool,
+}
+
+/// Assembles an Enhanced Packet Block's payload: the fixed-size record
+/// metadata `pad2pcapng` embeds ahead of the record data, followed by
+/// the data itself (used by both this tool and `verify`'s round-trip
+/// check to recover the original record's fields).
+fn block_data(record: &Record, record_data: Vec<u8>) -> Vec<u8> {
+    let mut data = Vec::with_capacity(20 + record_data.len());
+    data.extend_from_slice(&record.number.to_le_bytes());
+    data.extend_from_slice(&record.timestamp_ns.to_le_bytes());
+    data.extend_from_slice(&record.lfsr.to_le_bytes());
+    let value: u16 = if record.data_valid { 0x8000 } else { 0 } | record.data_valid_count;
+    data.extend_from_slice(&value.to_le_bytes());
+    data.extend_from_slice(&record.flags.to_le_bytes());
+    data.extend_from_slice(&record_data);
+    data
 }
 
 fn main() {
     let args = Args::parse();
 
+    let filter = match args.filter.as_deref().map(filter::Expr::parse) {
+        Some(Ok(expr)) => Some(expr),
+        Some(Err(error)) => {
+            eprintln!("Error parsing filter expression: {}", error);
+            return;
+        }
+        None => None,
+    };
+
     let mut pad_file = match PadFile::from_filename(&args.pad_file) {
         Ok(pf) => pf,
         Err(error) => {
@@ -58,192 +101,113 @@ fn main() {
         }
     };
 
-    // Section Header Block
-    {
-        pcapng_writer
-            .write_all(&(0x0a0d0d0a as u32).to_le_bytes())
-            .unwrap();
-
-        let mut sh_data: Vec<u8> = Vec::new();
-        sh_data.append(&mut (0x1a2b3c4d as u32).to_le_bytes().to_vec());
-        sh_data.append(&mut (0x0001 as u16).to_le_bytes().to_vec());
-        sh_data.append(&mut (0x0000 as u16).to_le_bytes().to_vec());
-        sh_data.append(&mut (-1 as i64).to_le_bytes().to_vec());
-
-        let sh_len: u32 = <usize as TryInto<u32>>::try_into(sh_data.len()).unwrap() + 4 * 3;
-        pcapng_writer.write_all(&sh_len.to_le_bytes()).unwrap();
-        pcapng_writer.write_all(&sh_data).unwrap();
-        pcapng_writer.write_all(&sh_len.to_le_bytes()).unwrap();
-    }
+    SectionHeaderBlock.write_to(&mut pcapng_writer).unwrap();
 
-    // Interface Description Block
-    {
-        pcapng_writer
-            .write_all(&(0x00000001 as u32).to_le_bytes())
-            .unwrap();
-
-        let mut if_data: Vec<u8> = Vec::new();
-        if_data.append(&mut (147 + 11 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (0x0000 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (0 as u32).to_le_bytes().to_vec());
-
-        // Options
-        let mut if_name = header.port_id.clone().into_bytes();
-        if_data.append(&mut (2 as u16).to_le_bytes().to_vec());
-        if_data.append(
-            &mut <usize as TryInto<u16>>::try_into(if_name.len())
-                .unwrap()
-                .to_le_bytes()
-                .to_vec(),
-        );
-        if_data.append(&mut if_name);
-        let padding_count = if if_data.len() % 4 != 0 {
-            4 - (if_data.len() % 4)
-        } else {
-            0
+    InterfaceDescriptionBlock {
+        link_type: 147 + 11,
+        if_name: header.port_id.clone(),
+        if_hardware: header.module_type.clone(),
+        tsresol: 9,
+    }
+    .write_to(&mut pcapng_writer)
+    .unwrap();
+
+    let mut current_record = pad_file.records.next();
+    while let Some(record) = current_record {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                eprintln!("Error reading record: {}", error);
+                break;
+            }
         };
-        for _ in 0..padding_count {
-            if_data.push(0);
-        }
+        assert_eq!(record.count, 1, "record \"count\" field is not equal to 1");
 
-        /*
-        if_data.append(&mut (8 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (8 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (2e9 as u64).to_le_bytes().to_vec());
-        */
-
-        if_data.append(&mut (9 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (1 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (9 as u32).to_le_bytes().to_vec());
-
-        let mut if_hardware = header.module_type.clone().into_bytes();
-        if_data.append(&mut (15 as u16).to_le_bytes().to_vec());
-        if_data.append(
-            &mut <usize as TryInto<u16>>::try_into(if_hardware.len())
-                .unwrap()
-                .to_le_bytes()
-                .to_vec(),
-        );
-        if_data.append(&mut if_hardware);
-        let padding_count = if if_data.len() % 4 != 0 {
-            4 - (if_data.len() % 4)
-        } else {
-            0
+        // Fetched now, before `record`'s data, so --check-descramble can
+        // compare against the LFSR state left after descrambling it.
+        let next_record = pad_file.records.next();
+        let next_lfsr = match &next_record {
+            Some(Ok(r)) => Some(r.lfsr),
+            _ => None,
         };
-        for _ in 0..padding_count {
-            if_data.push(0);
-        }
+        current_record = next_record;
 
-        if_data.append(&mut (0 as u16).to_le_bytes().to_vec());
-        if_data.append(&mut (0 as u16).to_le_bytes().to_vec());
+        let mut record_data = match pad_file.records.data_for(&record) {
+            Ok(data) => data,
+            Err(error) => {
+                eprintln!("Record {}: error reading data: {}", record.number, error);
+                continue;
+            }
+        };
 
-        let if_len: u32 = <usize as TryInto<u32>>::try_into(if_data.len()).unwrap() + 4 * 3;
-        pcapng_writer.write_all(&if_len.to_le_bytes()).unwrap();
-        pcapng_writer.write_all(&if_data).unwrap();
-        pcapng_writer.write_all(&if_len.to_le_bytes()).unwrap();
-    }
+        if args.check_descramble && !scramble::self_check(&record_data, record.lfsr, next_lfsr) {
+            eprintln!(
+                "Record {}: descrambled data's ending LFSR state did not match the next record's seed (lfsr: 0x{:04x})",
+                record.number, record.lfsr,
+            );
+        }
 
-    for record in pad_file.records {
-        assert_eq!(record.count, 1, "record \"count\" field is not equal to 1");
+        if args.descramble || args.check_descramble {
+            record_data = scramble::descramble(&record_data, record.lfsr);
+        }
 
-        let mut record_data = pad_file.record_reader.get_all_data_for_record(&record);
+        if let Some(expr) = &filter {
+            let ctx = filter::Context {
+                header: &header,
+                record: &record,
+                data: Some(&record_data),
+            };
+            if !expr.matches(&ctx) {
+                continue;
+            }
+        }
 
-        // Enhanced Packet Block
+        let decoded_comment = decode::decode(&record_data)
+            .iter()
+            .map(|packet| packet.to_string())
+            .collect::<Vec<String>>()
+            .join(" | ");
+
+        let mut comment = decoded_comment;
+        if (record.number == header.trigger_record_number)
+            || (record.number == header.first_record_number
+                && header.trigger_record_number < header.first_record_number)
+            || (record.number == header.last_record_number
+                && header.trigger_record_number > header.last_record_number)
         {
-            pcapng_writer
-                .write_all(&(0x00000006 as u32).to_le_bytes())
-                .unwrap();
-
-            let mut block_data: Vec<u8> = Vec::new();
-            block_data.append(&mut (0 as u32).to_le_bytes().to_vec());
-            block_data.append(
-                &mut <u64 as TryInto<u32>>::try_into(record.timestamp_ns.checked_shr(32).unwrap())
-                    .unwrap()
-                    .to_le_bytes()
-                    .to_vec(),
-            );
-            block_data.append(
-                &mut <u64 as TryInto<u32>>::try_into(record.timestamp_ns & ((1 << 32) - 1))
-                    .unwrap()
-                    .to_le_bytes()
-                    .to_vec(),
-            );
-            let record_data_len =
-                4 + 8 + 2 + 2 + 4 + <usize as TryInto<u32>>::try_into(record_data.len()).unwrap();
-            block_data.append(&mut record_data_len.to_le_bytes().to_vec());
-            block_data.append(&mut record_data_len.to_le_bytes().to_vec());
-
-            // Record metadata
-            block_data.append(&mut record.number.to_le_bytes().to_vec());
-            block_data.append(&mut record.timestamp_ns.to_le_bytes().to_vec());
-            block_data.append(&mut record.lfsr.to_le_bytes().to_vec());
-            let value: u16 = if record.data_valid { 0x8000 } else { 0 } | record.data_valid_count;
-            block_data.append(&mut value.to_le_bytes().to_vec());
-            block_data.append(&mut record.flags.to_le_bytes().to_vec());
-
-            // Record data
-            block_data.append(&mut record_data);
-            let padding_count = if block_data.len() % 4 != 0 {
-                4 - (block_data.len() % 4)
+            let trigger_comment = if header.timestamps_ns.trigger < record.timestamp_ns {
+                let difference_ns = record.timestamp_ns - header.timestamps_ns.trigger;
+                let ts_ns_int = difference_ns / 1000000000;
+                let ts_ns_frac = difference_ns % 1000000000;
+                format!(
+                    "Triggered {}.{:09}s before this record.",
+                    ts_ns_int, ts_ns_frac
+                )
+            } else if header.timestamps_ns.trigger == record.timestamp_ns {
+                "Triggered on this record.".to_string()
             } else {
-                0
+                let difference_ns = header.timestamps_ns.trigger - record.timestamp_ns;
+                let ts_ns_int = difference_ns / 1000000000;
+                let ts_ns_frac = difference_ns % 1000000000;
+                format!(
+                    "Triggered {}.{:09}s after this record.",
+                    ts_ns_int, ts_ns_frac
+                )
             };
-            for _ in 0..padding_count {
-                block_data.push(0);
-            }
-
-            if (record.number == header.trigger_record_number)
-                || (record.number == header.first_record_number
-                    && header.trigger_record_number < header.first_record_number)
-                || (record.number == header.last_record_number
-                    && header.trigger_record_number > header.last_record_number)
-            {
-                let mut packet_comment = if header.timestamps_ns.trigger < record.timestamp_ns {
-                    let difference_ns = record.timestamp_ns - header.timestamps_ns.trigger;
-                    let ts_ns_int = difference_ns / 1000000000;
-                    let ts_ns_frac = difference_ns % 1000000000;
-                    format!(
-                        "Triggered {}.{:09}s before this record.",
-                        ts_ns_int, ts_ns_frac
-                    )
-                } else if header.timestamps_ns.trigger == record.timestamp_ns {
-                    "Triggered on this record.".to_string()
-                } else {
-                    let difference_ns = header.timestamps_ns.trigger - record.timestamp_ns;
-                    let ts_ns_int = difference_ns / 1000000000;
-                    let ts_ns_frac = difference_ns % 1000000000;
-                    format!(
-                        "Triggered {}.{:09}s after this record.",
-                        ts_ns_int, ts_ns_frac
-                    )
-                }
-                .into_bytes();
-                block_data.append(&mut (1 as u16).to_le_bytes().to_vec());
-                block_data.append(
-                    &mut <usize as TryInto<u16>>::try_into(packet_comment.len())
-                        .unwrap()
-                        .to_le_bytes()
-                        .to_vec(),
-                );
-                block_data.append(&mut packet_comment);
-                let padding_count = if block_data.len() % 4 != 0 {
-                    4 - (block_data.len() % 4)
-                } else {
-                    0
-                };
-                for _ in 0..padding_count {
-                    block_data.push(0);
-                }
-
-                block_data.append(&mut (0 as u16).to_le_bytes().to_vec());
-                block_data.append(&mut (0 as u16).to_le_bytes().to_vec());
-            }
+            comment = if comment.is_empty() {
+                trigger_comment
+            } else {
+                format!("{} | {}", comment, trigger_comment)
+            };
+        }
 
-            let block_len: u32 =
-                <usize as TryInto<u32>>::try_into(block_data.len()).unwrap() + 4 * 3;
-            pcapng_writer.write_all(&block_len.to_le_bytes()).unwrap();
-            pcapng_writer.write_all(&block_data).unwrap();
-            pcapng_writer.write_all(&block_len.to_le_bytes()).unwrap();
+        EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp_ns: record.timestamp_ns,
+            data: block_data(&record, record_data),
+            comment: Some(comment),
         }
+        .write_to(&mut pcapng_writer)
+        .unwrap();
     }
 }
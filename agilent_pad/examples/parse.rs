@@ -2,7 +2,7 @@
 
 /*
  *  parse.rs - Parser demo for Agilent PAD files.
- *  Copyright (C) 2023  Forest Crossman <cyrozap@gmail.com>
+ *  Copyright (C) 2023-2024  Forest Crossman <cyrozap@gmail.com>
  *
  *  This program is free software: you can redistribute it and/or modify
  *  it under the terms of the GNU General Public License as published by
@@ -18,10 +18,6 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
-
 use clap::Parser;
 
 use agilent_pad::*;
@@ -31,79 +27,115 @@ use agilent_pad::*;
 struct Args {
     /// The PAD file to read.
     pad_file: String,
+
+    /// Only print records matching this filter expression, e.g.
+    /// `dir == us and tlp == mem_wr`.
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Undo the PCIe data scrambler before decoding each record, using
+    /// its captured `lfsr` seed.
+    #[arg(long)]
+    descramble: bool,
+
+    /// Run each record's captured bytes through the descrambler from its
+    /// `lfsr` seed and report any record whose ending LFSR state doesn't
+    /// match the next record's seed. Implies `--descramble`.
+    #[arg(long)]
+    check_descramble: bool,
 }
 
 fn get_bit(value: u32, bit: usize) -> bool {
     value & (1 << bit) != 0
 }
 
-fn char_for_nybble(value: u8) -> char {
-    match value {
-        0 => '0',
-        1 => '1',
-        2 => '2',
-        3 => '3',
-        4 => '4',
-        5 => '5',
-        6 => '6',
-        7 => '7',
-        8 => '8',
-        9 => '9',
-        0xa => 'a',
-        0xb => 'b',
-        0xc => 'c',
-        0xd => 'd',
-        0xe => 'e',
-        0xf => 'f',
-        _ => '?',
-    }
-}
-
 fn main() {
     let args = Args::parse();
 
-    let mut pad_file = match File::open(&args.pad_file) {
-        Ok(f) => f,
-        Err(error) => {
-            eprintln!("Error opening file {:?}: {:?}", &args.pad_file, error);
+    let filter = match args.filter.as_deref().map(filter::Expr::parse) {
+        Some(Ok(expr)) => Some(expr),
+        Some(Err(error)) => {
+            eprintln!("Error parsing filter expression: {}", error);
             return;
         }
+        None => None,
     };
 
-    let mut pad_file_2 = match File::open(&args.pad_file) {
-        Ok(f) => f,
+    let mut pad_file = match PadFile::from_filename(&args.pad_file) {
+        Ok(pf) => pf,
         Err(error) => {
             eprintln!("Error opening file {:?}: {:?}", &args.pad_file, error);
             return;
         }
     };
 
-    let header = parse_header(&mut pad_file).unwrap();
-    println!("{:?}", header);
+    println!("{:?}", pad_file.header);
 
-    pad_file
-        .seek(std::io::SeekFrom::Start(header.records_offset))
-        .unwrap();
-    let mut pad_reader = BufReader::new(pad_file);
+    let mut prev_timestamp_ns = None;
+    let mut current_record = pad_file.records.next();
+    while let Some(record) = current_record {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                eprintln!("Error reading record: {}", error);
+                break;
+            }
+        };
 
-    pad_file_2
-        .seek(std::io::SeekFrom::Start(header.record_data_offset))
-        .unwrap();
-    let mut data_reader = BufReader::new(pad_file_2);
+        // Fetched now, before `record`'s data, so --check-descramble can
+        // compare against the LFSR state left after descrambling it.
+        let next_record = pad_file.records.next();
+        let next_lfsr = match &next_record {
+            Some(Ok(r)) => Some(r.lfsr),
+            _ => None,
+        };
+        current_record = next_record;
+
+        let data = if record.data_valid {
+            match pad_file.records.valid_data_for(&record) {
+                Ok(data) => Some(data),
+                Err(error) => {
+                    eprintln!("Record {}: error reading data: {}", record.number, error);
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
 
-    let mut prev_timestamp_ns = None;
-    let mut current_offset: i64 = 0;
-    for record_number in header.first_record_number..=header.last_record_number {
-        let mut record_buffer = [0; 40];
-        pad_reader.read_exact(&mut record_buffer).unwrap();
-        if record_buffer.iter().all(|b| *b == 0) {
-            println!("Encountered empty record, exiting...");
-            break;
+        if (args.descramble || args.check_descramble) && record.data_valid {
+            let raw_data = match pad_file.records.data_for(&record) {
+                Ok(data) => data,
+                Err(error) => {
+                    eprintln!("Record {}: error reading data: {}", record.number, error);
+                    continue;
+                }
+            };
+            if args.check_descramble && !scramble::self_check(&raw_data, record.lfsr, next_lfsr) {
+                eprintln!(
+                    "Record {}: descrambled data's ending LFSR state did not match the next record's seed (lfsr: 0x{:04x})",
+                    record.number, record.lfsr,
+                );
+            }
         }
 
-        let record = Record::from_slice(&record_buffer).unwrap();
+        let data = if args.descramble || args.check_descramble {
+            data.map(|d| scramble::descramble(&d, record.lfsr))
+        } else {
+            data
+        };
 
-        assert_eq!(record.number, record_number);
+        if let Some(expr) = &filter {
+            let ctx = filter::Context {
+                header: &pad_file.header,
+                record: &record,
+                data: data.as_deref(),
+            };
+            if !expr.matches(&ctx) {
+                prev_timestamp_ns = Some(record.timestamp_ns);
+                continue;
+            }
+        }
 
         let us_ds = match get_bit(record.flags, 28) {
             true => "US",
@@ -117,22 +149,10 @@ fn main() {
             prev_timestamp_ns = Some(record.timestamp_ns);
         }
 
-        let record_data = if record.data_valid {
-            data_reader
-                .seek_relative(
-                    <u64 as TryInto<i64>>::try_into(record.data_offset).unwrap() - current_offset,
-                )
-                .unwrap();
-            let mut data: Vec<u8> = vec![0; record.data_valid_count.into()];
-            data_reader.read_exact(data.as_mut_slice()).unwrap();
-            current_offset = <u64 as TryInto<i64>>::try_into(record.data_offset).unwrap()
-                + <usize as TryInto<i64>>::try_into(data.len()).unwrap();
-
-            let mut ret = String::with_capacity(2 + 2 * data.len());
-            ret.push_str(": ");
-            for b in data.iter() {
-                ret.push(char_for_nybble(b >> 4));
-                ret.push(char_for_nybble(b & 0xf));
+        let record_data = if let Some(data) = &data {
+            let mut ret = String::from(": ");
+            for packet in decode::decode(data) {
+                ret.push_str(&format!("{} | ", packet));
             }
             ret
         } else {
@@ -140,10 +160,7 @@ fn main() {
         };
 
         let debug_data = format!(
-            " (unk0: 0x{:016x}, unk3: {:02x}{:02x}, bytes_valid: {} ({}), flags: 0x{:08x}, data_offset: {})",
-            record.unk0,
-            record.unk3[0],
-            record.unk3[1],
+            " (bytes_valid: {} ({}), flags: 0x{:08x}, data_offset: {})",
             record.data_valid_count,
             match record.data_valid {
                 true => 1,
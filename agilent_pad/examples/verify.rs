@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  verify.rs - Check a pad2pcapng conversion round-trips losslessly.
+ *  Copyright (C) 2024  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use agilent_pad::*;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The original PAD file.
+    pad_file: String,
+
+    /// The pcapng file produced from `pad_file` by `pad2pcapng`.
+    pcapng_file: String,
+}
+
+fn is_trigger_record(header: &PadHeader, number: u32) -> bool {
+    (number == header.trigger_record_number)
+        || (number == header.first_record_number
+            && header.trigger_record_number < header.first_record_number)
+        || (number == header.last_record_number
+            && header.trigger_record_number > header.last_record_number)
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let mut pad_file = match PadFile::from_filename(&args.pad_file) {
+        Ok(pf) => pf,
+        Err(error) => {
+            eprintln!("Error opening file {:?}: {:?}", &args.pad_file, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let converted = match verify::read_record_metadata(&args.pcapng_file) {
+        Ok(records) => records,
+        Err(error) => {
+            eprintln!("Error reading file {:?}: {}", &args.pcapng_file, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut converted = converted.into_iter();
+    let mut n = 0;
+
+    for record in &mut pad_file.records {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                eprintln!("Error reading record from {:?}: {}", &args.pad_file, error);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        let converted_record = match converted.next() {
+            Some(record) => record,
+            None => {
+                eprintln!(
+                    "Record {}: present in PAD file but missing from pcapng",
+                    record.number
+                );
+                return ExitCode::FAILURE;
+            }
+        };
+
+        macro_rules! check_field {
+            ($field:ident) => {
+                if record.$field != converted_record.$field {
+                    eprintln!(
+                        "Record {}: field {:?} mismatch: PAD has {:?}, pcapng has {:?}",
+                        record.number,
+                        stringify!($field),
+                        record.$field,
+                        converted_record.$field,
+                    );
+                    return ExitCode::FAILURE;
+                }
+            };
+        }
+
+        check_field!(number);
+        check_field!(timestamp_ns);
+        check_field!(lfsr);
+        check_field!(data_valid);
+        check_field!(data_valid_count);
+        check_field!(flags);
+
+        if is_trigger_record(&pad_file.header, record.number) && !converted_record.has_comment {
+            eprintln!(
+                "Record {}: expected a trigger-record comment in pcapng, found none",
+                record.number
+            );
+            return ExitCode::FAILURE;
+        }
+
+        n += 1;
+    }
+
+    if let Some(extra) = converted.next() {
+        eprintln!(
+            "Record {}: present in pcapng but missing from PAD file",
+            extra.number
+        );
+        return ExitCode::FAILURE;
+    }
+
+    println!("OK: {} records match.", n);
+    ExitCode::SUCCESS
+}
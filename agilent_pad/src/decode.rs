@@ -0,0 +1,425 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/decode.rs - PCIe TLP/DLLP decoder for Agilent PAD record payloads.
+ *  Copyright (C) 2024  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Best-effort decoder for the PCIe link-layer byte stream captured in a
+//! record's data payload. Operates on the descrambled (8b/10b data-only)
+//! byte stream and recognizes framing tokens, Transaction Layer Packets,
+//! Data Link Layer Packets, and ordered sets.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+// PCIe 8b/10b special (K-code) symbol values, as they appear in the
+// descrambled byte stream.
+pub(crate) const COM: u8 = 0xbc; // K28.5 - Comma, starts an ordered set
+const STP: u8 = 0xfb; // K27.7 - Start TLP
+const SDP: u8 = 0x5c; // K28.2 - Start DLLP
+const END: u8 = 0xfd; // K29.7 - End
+const EDB: u8 = 0xfe; // K30.7 - End Bad
+const PAD_K: u8 = 0xf7; // K23.7 - Pad, used in EIEOS
+pub(crate) const SKP: u8 = 0x1c; // K28.0 - Skip
+const FTS: u8 = 0x3c; // K28.1 - Fast Training Sequence
+
+/// A decoded Transaction Layer Packet header.
+#[derive(Debug, Clone)]
+pub struct Tlp {
+    pub fmt: u8,
+    pub tlp_type: u8,
+    pub tc: u8,
+    pub length_dw: u16,
+    pub requester_id: Option<u16>,
+    pub completer_id: Option<u16>,
+    pub tag: Option<u8>,
+    pub first_be: Option<u8>,
+    pub last_be: Option<u8>,
+    pub address: Option<u64>,
+    pub register_number: Option<u16>,
+}
+
+impl Tlp {
+    /// A short, stable name for the TLP type, as used by capture filter
+    /// expressions (e.g. `tlp == mem_wr`).
+    pub fn type_name(&self) -> &'static str {
+        let has_data = self.fmt == 0b10 || self.fmt == 0b11;
+        match self.tlp_type {
+            0x00 if has_data => "mem_wr",
+            0x00 => "mem_rd",
+            0x01 if has_data => "mem_wr_lk",
+            0x01 => "mem_rd_lk",
+            0x02 if has_data => "io_wr",
+            0x02 => "io_rd",
+            0x04..=0x05 if has_data => "cfg_wr",
+            0x04..=0x05 => "cfg_rd",
+            0x0a..=0x0b if has_data => "cpld",
+            0x0a..=0x0b => "cpl",
+            _ => "other",
+        }
+    }
+}
+
+impl fmt::Display for Tlp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TLP fmt={} type=0x{:02x} tc={}", self.fmt, self.tlp_type, self.tc)?;
+        if let Some(requester_id) = self.requester_id {
+            write!(f, " requester=0x{:04x}", requester_id)?;
+        }
+        if let Some(completer_id) = self.completer_id {
+            write!(f, " completer=0x{:04x}", completer_id)?;
+        }
+        if let Some(tag) = self.tag {
+            write!(f, " tag={}", tag)?;
+        }
+        if let Some(address) = self.address {
+            write!(f, " addr=0x{:x}", address)?;
+        }
+        if let Some(register_number) = self.register_number {
+            write!(f, " reg={}", register_number)?;
+        }
+        write!(f, " len={}dw", self.length_dw)
+    }
+}
+
+/// A decoded Data Link Layer Packet.
+#[derive(Debug, Clone)]
+pub enum Dllp {
+    Ack { seq_num: u16 },
+    Nak { seq_num: u16 },
+    InitFc1 { vc: u8, hdr_fc: u16, data_fc: u16 },
+    InitFc2 { vc: u8, hdr_fc: u16, data_fc: u16 },
+    UpdateFc { vc: u8, hdr_fc: u16, data_fc: u16 },
+    PowerManagement { dllp_type: u8 },
+    Other { dllp_type: u8 },
+}
+
+impl fmt::Display for Dllp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dllp::Ack { seq_num } => write!(f, "Ack seq={}", seq_num),
+            Dllp::Nak { seq_num } => write!(f, "Nak seq={}", seq_num),
+            Dllp::InitFc1 { vc, hdr_fc, data_fc } => {
+                write!(f, "InitFC1 vc={} hdr_fc={} data_fc={}", vc, hdr_fc, data_fc)
+            }
+            Dllp::InitFc2 { vc, hdr_fc, data_fc } => {
+                write!(f, "InitFC2 vc={} hdr_fc={} data_fc={}", vc, hdr_fc, data_fc)
+            }
+            Dllp::UpdateFc { vc, hdr_fc, data_fc } => {
+                write!(f, "UpdateFC vc={} hdr_fc={} data_fc={}", vc, hdr_fc, data_fc)
+            }
+            Dllp::PowerManagement { dllp_type } => write!(f, "PM 0x{:02x}", dllp_type),
+            Dllp::Other { dllp_type } => write!(f, "DLLP 0x{:02x}", dllp_type),
+        }
+    }
+}
+
+/// A decoded PCIe ordered set.
+#[derive(Debug, Clone)]
+pub enum OrderedSet {
+    Ts1 { link: Option<u8>, lane: Option<u8> },
+    Ts2 { link: Option<u8>, lane: Option<u8> },
+    Skp,
+    Fts,
+    Eieos,
+}
+
+impl fmt::Display for OrderedSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderedSet::Ts1 { link, lane } => write!(f, "TS1 link={:?} lane={:?}", link, lane),
+            OrderedSet::Ts2 { link, lane } => write!(f, "TS2 link={:?} lane={:?}", link, lane),
+            OrderedSet::Skp => write!(f, "SKP"),
+            OrderedSet::Fts => write!(f, "FTS"),
+            OrderedSet::Eieos => write!(f, "EIEOS"),
+        }
+    }
+}
+
+/// A single decoded unit of the link-layer byte stream.
+#[derive(Debug, Clone)]
+pub enum Packet<'a> {
+    Tlp(Tlp),
+    Dllp(Dllp),
+    OrderedSet(OrderedSet),
+    /// Bytes that could not be parsed as a TLP, DLLP, or ordered set.
+    /// Decoding resumes at the next recognized framing token.
+    Unknown { bytes: &'a [u8] },
+}
+
+impl fmt::Display for Packet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Tlp(tlp) => write!(f, "{}", tlp),
+            Packet::Dllp(dllp) => write!(f, "{}", dllp),
+            Packet::OrderedSet(os) => write!(f, "{}", os),
+            Packet::Unknown { bytes } => write!(f, "Unknown ({} bytes)", bytes.len()),
+        }
+    }
+}
+
+fn is_framing_token(b: u8) -> bool {
+    matches!(b, STP | SDP | COM | SKP | FTS | PAD_K)
+}
+
+fn parse_tlp(data: &[u8]) -> Option<(Tlp, usize)> {
+    // data[0] == STP; the header DW follows.
+    if data.len() < 5 {
+        return None;
+    }
+
+    let header = &data[1..5];
+    let fmt = (header[0] >> 5) & 0x3;
+    let tlp_type = header[0] & 0x1f;
+    let tc = (header[1] >> 4) & 0x7;
+    let length_dw = ((header[2] & 0x3) as u16) << 8 | header[3] as u16;
+
+    let four_dw = fmt == 0b01 || fmt == 0b11;
+    let header_len = if four_dw { 16 } else { 12 };
+    if data.len() < 1 + header_len {
+        return None;
+    }
+    let body = &data[5..1 + header_len];
+
+    let (
+        requester_id,
+        completer_id,
+        tag,
+        first_be,
+        last_be,
+        address,
+        register_number,
+    ) = match tlp_type {
+        // Memory / IO read or write (Rd vs Wr is distinguished by Fmt,
+        // not Type)
+        0x00..=0x02 => {
+            let requester_id = Some(u16::from_be_bytes([body[0], body[1]]));
+            let tag = Some(body[2]);
+            let first_be = Some(body[3] & 0xf);
+            let last_be = Some(body[3] >> 4);
+            let address = if four_dw {
+                Some(u64::from_be_bytes([
+                    body[4], body[5], body[6], body[7], body[8], body[9], body[10], body[11],
+                ]))
+            } else {
+                Some(u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as u64)
+            };
+            (requester_id, None, tag, first_be, last_be, address, None)
+        }
+        // Configuration read or write (type 0b0_0100 / 0b0_0101)
+        0x04..=0x05 => {
+            let requester_id = Some(u16::from_be_bytes([body[0], body[1]]));
+            let tag = Some(body[2]);
+            let first_be = Some(body[3] & 0xf);
+            let last_be = Some(body[3] >> 4);
+            let register_number = Some((u16::from_be_bytes([body[6], body[7]]) >> 2) & 0x3ff);
+            (
+                requester_id,
+                None,
+                tag,
+                first_be,
+                last_be,
+                None,
+                register_number,
+            )
+        }
+        // Completion / completion with data
+        0x0a..=0x0b => {
+            let completer_id = Some(u16::from_be_bytes([body[0], body[1]]));
+            let requester_id = Some(u16::from_be_bytes([body[4], body[5]]));
+            let tag = Some(body[6]);
+            (
+                requester_id,
+                completer_id,
+                tag,
+                None,
+                None,
+                None,
+                None,
+            )
+        }
+        _ => (None, None, None, None, None, None, None),
+    };
+
+    let tlp = Tlp {
+        fmt,
+        tlp_type,
+        tc,
+        length_dw,
+        requester_id,
+        completer_id,
+        tag,
+        first_be,
+        last_be,
+        address,
+        register_number,
+    };
+
+    // header DW(s) + optional data (for *_data fmt codes), followed by a
+    // 4-byte LCRC and the END framing byte. We only need to skip past our
+    // own header/data here; resynchronization handles the rest if this
+    // estimate is wrong.
+    let has_data = fmt == 0b10 || fmt == 0b11;
+    let data_len = if has_data {
+        length_dw as usize * 4
+    } else {
+        0
+    };
+    let consumed = 1 + header_len + data_len + 4 + 1;
+    Some((tlp, consumed.min(data.len())))
+}
+
+fn parse_dllp(data: &[u8]) -> Option<(Dllp, usize)> {
+    // data[0] == SDP, followed by the DLLP type byte, a 3-byte payload,
+    // and a 2-byte CRC; the END after them is left for `decode` to skip.
+    if data.len() < 7 {
+        return None;
+    }
+    let dllp_type = data[1];
+    let payload = &data[2..5];
+
+    let dllp = match dllp_type {
+        0x00 => Dllp::Ack {
+            seq_num: u16::from_be_bytes([payload[0] & 0xf, payload[1]]),
+        },
+        0x10 => Dllp::Nak {
+            seq_num: u16::from_be_bytes([payload[0] & 0xf, payload[1]]),
+        },
+        0x40..=0x47 => Dllp::InitFc1 {
+            vc: dllp_type & 0x7,
+            hdr_fc: u16::from_be_bytes([payload[0] & 0x3, payload[1]]),
+            data_fc: u16::from_be_bytes([0, payload[2]]),
+        },
+        0x50..=0x57 => Dllp::InitFc2 {
+            vc: dllp_type & 0x7,
+            hdr_fc: u16::from_be_bytes([payload[0] & 0x3, payload[1]]),
+            data_fc: u16::from_be_bytes([0, payload[2]]),
+        },
+        0x60..=0x67 => Dllp::UpdateFc {
+            vc: dllp_type & 0x7,
+            hdr_fc: u16::from_be_bytes([payload[0] & 0x3, payload[1]]),
+            data_fc: u16::from_be_bytes([0, payload[2]]),
+        },
+        0x20..=0x23 => Dllp::PowerManagement { dllp_type },
+        _ => Dllp::Other { dllp_type },
+    };
+
+    Some((dllp, 7))
+}
+
+fn parse_ordered_set(data: &[u8]) -> Option<(OrderedSet, usize)> {
+    // data[0] == COM; TS1/TS2 are 16 symbols total: COM, link, lane,
+    // N_FTS (an arbitrary count, not an identifier), data rate, training
+    // control, then 10 repeats of the TS1 (D10.2/0x4a) or TS2
+    // (D5.2/0x45) identifier symbol starting at symbol 6.
+    if data.len() < 7 {
+        return None;
+    }
+    let link = if data[1] == 0xff { None } else { Some(data[1]) };
+    let lane = if data[2] == 0xff { None } else { Some(data[2]) };
+
+    let consumed = 16.min(data.len());
+    match data[6] {
+        0x4a => Some((OrderedSet::Ts1 { link, lane }, consumed)),
+        0x45 => Some((OrderedSet::Ts2 { link, lane }, consumed)),
+        _ => None,
+    }
+}
+
+/// Skip forward to the next recognized framing token, returning the
+/// skipped bytes and how many bytes were consumed (at least 1, so the
+/// decoder always makes forward progress).
+fn resync(data: &[u8]) -> (&[u8], usize) {
+    let skip = data[1..]
+        .iter()
+        .position(|b| is_framing_token(*b))
+        .map(|p| p + 1)
+        .unwrap_or(data.len());
+    (&data[..skip], skip)
+}
+
+/// Decode a descrambled link-layer byte stream into a sequence of
+/// packets. This is fallible and resynchronizing: malformed or truncated
+/// framing is reported as `Packet::Unknown` and decoding resumes at the
+/// next framing token rather than panicking, so a corrupt capture still
+/// parses in full.
+pub fn decode(data: &[u8]) -> Vec<Packet<'_>> {
+    let mut packets = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        let rest = &data[i..];
+        match rest[0] {
+            STP => match parse_tlp(rest) {
+                Some((tlp, consumed)) => {
+                    packets.push(Packet::Tlp(tlp));
+                    i += consumed;
+                }
+                None => {
+                    let (bytes, consumed) = resync(rest);
+                    packets.push(Packet::Unknown { bytes });
+                    i += consumed;
+                }
+            },
+            SDP => match parse_dllp(rest) {
+                Some((dllp, consumed)) => {
+                    packets.push(Packet::Dllp(dllp));
+                    i += consumed;
+                }
+                None => {
+                    let (bytes, consumed) = resync(rest);
+                    packets.push(Packet::Unknown { bytes });
+                    i += consumed;
+                }
+            },
+            COM => match parse_ordered_set(rest) {
+                Some((os, consumed)) => {
+                    packets.push(Packet::OrderedSet(os));
+                    i += consumed;
+                }
+                None => {
+                    let (bytes, consumed) = resync(rest);
+                    packets.push(Packet::Unknown { bytes });
+                    i += consumed;
+                }
+            },
+            SKP => {
+                packets.push(Packet::OrderedSet(OrderedSet::Skp));
+                i += 1;
+            }
+            FTS => {
+                packets.push(Packet::OrderedSet(OrderedSet::Fts));
+                i += 1;
+            }
+            PAD_K => {
+                let run = rest.iter().take_while(|b| **b == PAD_K).count();
+                packets.push(Packet::OrderedSet(OrderedSet::Eieos));
+                i += run;
+            }
+            END | EDB => {
+                i += 1;
+            }
+            _ => {
+                let (bytes, consumed) = resync(rest);
+                packets.push(Packet::Unknown { bytes });
+                i += consumed;
+            }
+        }
+    }
+
+    packets
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/decoder.rs - Cursor-based byte decoder for PAD's binary formats.
+ *  Copyright (C) 2025  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A small cursor over a byte slice, for decoding the big-endian,
+//! length-prefixed fields PAD headers are built from. Each
+//! `decode_*` method either advances the cursor and returns the decoded
+//! value, or leaves the cursor untouched and reports how many
+//! additional bytes the caller needs to supply before retrying —
+//! callers never need to re-decode fields that already succeeded.
+
+use alloc::string::String;
+
+/// A cursor over a byte slice that decodes PAD's binary fields.
+pub struct Decoder<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// A decoder starting at the beginning of `buf`.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, offset: 0 }
+    }
+
+    /// A decoder resuming at `offset` into `buf`, e.g. after a previous
+    /// decoder over a shorter version of the same buffer ran out of
+    /// input partway through a field.
+    pub fn with_offset(buf: &'a [u8], offset: usize) -> Self {
+        Self { buf, offset }
+    }
+
+    /// The cursor's current position in the underlying buffer.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The number of unconsumed bytes left in the buffer.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.offset
+    }
+
+    fn decode_bytes(&mut self, n: usize) -> Result<&'a [u8], usize> {
+        if self.remaining() < n {
+            return Err(n - self.remaining());
+        }
+        let bytes = &self.buf[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(bytes)
+    }
+
+    /// Decode an `n`-byte (`n <= 8`) big-endian unsigned integer.
+    ///
+    /// On success, advances the cursor by `n` bytes. On failure, the
+    /// cursor is left untouched and the error is the number of
+    /// additional bytes needed.
+    pub fn decode_uint_be(&mut self, n: usize) -> Result<u64, usize> {
+        let bytes = self.decode_bytes(n)?;
+        Ok(bytes.iter().fold(0_u64, |acc, b| (acc << 8) | u64::from(*b)))
+    }
+
+    /// Decode a 64-bit value stored as two big-endian `u32` halves
+    /// (high word first). `Record`'s `count`/`timestamp_ns`/`data_offset`
+    /// hi/lo pairs are little-endian and are parsed by
+    /// `Record::from_slice`, not by this method.
+    pub fn decode_u64_hi_lo(&mut self) -> Result<u64, usize> {
+        let start = self.offset;
+        let hi = self.decode_uint_be(4).inspect_err(|_| self.offset = start)?;
+        let lo = self.decode_uint_be(4).inspect_err(|_| self.offset = start)?;
+        Ok((hi << 32) | lo)
+    }
+
+    /// Decode a big-endian `u16` length prefix followed by that many
+    /// bytes, the layout PAD uses for its header strings.
+    pub fn decode_length_prefixed_be16(&mut self) -> Result<&'a [u8], usize> {
+        let start = self.offset;
+        let len = self.decode_uint_be(2).inspect_err(|_| self.offset = start)?;
+        self.decode_bytes(len as usize)
+            .inspect_err(|_| self.offset = start)
+    }
+
+    /// Decode a fixed 8-byte big-endian unsigned integer. A thin alias
+    /// over `decode_uint_be(8)` for the many header fields that are
+    /// plain 64-bit values rather than the hi/lo-word pairs `Record`
+    /// uses.
+    pub fn decode_u64(&mut self) -> Result<u64, usize> {
+        self.decode_uint_be(8)
+    }
+
+    /// Decode a length-prefixed header string and lossily convert it
+    /// to an owned `String`, saving callers the
+    /// `String::from_utf8_lossy(...).into_owned()` boilerplate.
+    pub fn decode_string(&mut self) -> Result<String, usize> {
+        self.decode_length_prefixed_be16()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/error.rs - Error type for the Agilent PAD parsing library.
+ *  Copyright (C) 2024-2025  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use core::fmt;
+
+/// Errors produced while parsing a PAD record or header from a byte
+/// slice. Kept `no_std`-compatible so the decoder can be reused outside
+/// of a full `std` environment; the `std`-only `Io` variant carries
+/// errors from the `Read`/`Seek` convenience wrappers.
+#[derive(Debug)]
+pub enum PadError {
+    /// The input did not match the expected binary layout. `offset` is
+    /// how many bytes into the input the mismatch was detected.
+    Parse { offset: usize },
+    /// The input ended before a complete record or header could be read.
+    Truncated,
+    /// A record table entry's `number` field didn't match the record
+    /// number the reader expected next.
+    UnexpectedRecordNumber { expected: u32, found: u32 },
+    /// The header's `record_len` field wasn't the expected 40 bytes.
+    BadRecordLen,
+    /// The header's `timestamp_array_size` field wasn't the expected 8.
+    BadTimestampArraySize,
+    /// A record index passed to `PadFile::record_data` (or similar) was
+    /// past the end of the indexed record table.
+    IndexOutOfRange(usize),
+    /// An I/O error occurred while reading from a file or stream.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl fmt::Display for PadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PadError::Parse { offset } => {
+                write!(f, "failed to parse PAD data at offset {}", offset)
+            }
+            PadError::Truncated => write!(f, "PAD data ended unexpectedly"),
+            PadError::UnexpectedRecordNumber { expected, found } => write!(
+                f,
+                "record number mismatch: expected {}, found {}",
+                expected, found
+            ),
+            PadError::BadRecordLen => write!(f, "record length mismatch"),
+            PadError::BadTimestampArraySize => write!(f, "timestamp array size mismatch"),
+            PadError::IndexOutOfRange(index) => {
+                write!(f, "record index {} is out of range", index)
+            }
+            #[cfg(feature = "std")]
+            PadError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PadError {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for PadError {
+    fn from(e: std::io::Error) -> Self {
+        PadError::Io(e)
+    }
+}
@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/filter.rs - Capture filter expressions for PAD record streams.
+ *  Copyright (C) 2024  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A small predicate language for selecting which records a capture
+//! conversion or dump should emit, e.g. `dir == us and number >= 100`.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::decode;
+use crate::{PadHeader, Record};
+
+fn get_bit(value: u32, bit: usize) -> bool {
+    value & (1 << bit) != 0
+}
+
+/// A comparison operator appearing in a `Expr::Compare` term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// The right-hand side of a `Expr::Compare` term.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Ident(String),
+    Int(i128),
+}
+
+/// The left-hand side of a `Expr::Compare` term.
+#[derive(Debug, Clone, Copy)]
+pub enum Field {
+    Dir,
+    Number,
+    TimestampNs,
+    TriggerOffsetNs,
+    Tlp,
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Compare(Field, Op, Value),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+/// Context a filter expression is evaluated against. `data` is the
+/// record's payload, or `None` when the caller has none for it; a `tlp`
+/// term (with either `==` or `!=`) never matches when `data` is `None`.
+pub struct Context<'a> {
+    pub header: &'a PadHeader,
+    pub record: &'a Record,
+    pub data: Option<&'a [u8]>,
+}
+
+impl Expr {
+    /// Parse a filter expression from its textual form.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        let mut pos = 0;
+        let expr = parse_or(&tokens, &mut pos)?;
+        if pos != tokens.len() {
+            return Err(format!("unexpected token: {:?}", tokens[pos]));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate the expression against a record.
+    pub fn matches(&self, ctx: &Context) -> bool {
+        match self {
+            Expr::Compare(field, op, value) => compare(*field, *op, value, ctx),
+            Expr::And(a, b) => a.matches(ctx) && b.matches(ctx),
+            Expr::Or(a, b) => a.matches(ctx) || b.matches(ctx),
+            Expr::Not(a) => !a.matches(ctx),
+        }
+    }
+}
+
+fn compare(field: Field, op: Op, value: &Value, ctx: &Context) -> bool {
+    match field {
+        Field::Dir => {
+            let dir = if get_bit(ctx.record.flags, 28) { "us" } else { "ds" };
+            match value {
+                Value::Ident(ident) => match op {
+                    Op::Eq => dir == ident,
+                    Op::Ne => dir != ident,
+                    _ => false,
+                },
+                Value::Int(_) => false,
+            }
+        }
+        Field::Number => compare_int(ctx.record.number as i128, op, value),
+        Field::TimestampNs => compare_int(ctx.record.timestamp_ns as i128, op, value),
+        Field::TriggerOffsetNs => {
+            let offset = ctx.record.timestamp_ns as i128 - ctx.header.timestamps_ns.trigger as i128;
+            compare_int(offset, op, value)
+        }
+        Field::Tlp => match (op, value) {
+            (Op::Eq, Value::Ident(_)) | (Op::Ne, Value::Ident(_)) if ctx.data.is_none() => false,
+            (Op::Eq, Value::Ident(ident)) | (Op::Ne, Value::Ident(ident)) => {
+                let is_match = ctx.data.is_some_and(|data| {
+                    decode::decode(data)
+                        .iter()
+                        .any(|packet| matches!(packet, decode::Packet::Tlp(tlp) if tlp.type_name() == ident))
+                });
+                if op == Op::Eq {
+                    is_match
+                } else {
+                    !is_match
+                }
+            }
+            _ => false,
+        },
+    }
+}
+
+fn compare_int(lhs: i128, op: Op, value: &Value) -> bool {
+    let rhs = match value {
+        Value::Int(n) => *n,
+        Value::Ident(_) => return false,
+    };
+    match op {
+        Op::Eq => lhs == rhs,
+        Op::Ne => lhs != rhs,
+        Op::Lt => lhs < rhs,
+        Op::Le => lhs <= rhs,
+        Op::Gt => lhs > rhs,
+        Op::Ge => lhs >= rhs,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '=' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(Op::Eq));
+                i += 2;
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(Op::Ne));
+                i += 2;
+            }
+            '<' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(Op::Le));
+                i += 2;
+            }
+            '>' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(Op::Ge));
+                i += 2;
+            }
+            '<' => {
+                tokens.push(Token::Op(Op::Lt));
+                i += 1;
+            }
+            '>' => {
+                tokens.push(Token::Op(Op::Gt));
+                i += 1;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<i128>()
+                    .map_err(|e| format!("invalid integer {:?}: {}", text, e))?;
+                tokens.push(Token::Int(value));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word),
+                });
+            }
+            _ => return Err(format!("unexpected character: {:?}", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_or(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    let mut lhs = parse_and(tokens, pos)?;
+    while tokens.get(*pos) == Some(&Token::Or) {
+        *pos += 1;
+        let rhs = parse_and(tokens, pos)?;
+        lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+    }
+    Ok(lhs)
+}
+
+fn parse_and(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    let mut lhs = parse_unary(tokens, pos)?;
+    while tokens.get(*pos) == Some(&Token::And) {
+        *pos += 1;
+        let rhs = parse_unary(tokens, pos)?;
+        lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+    }
+    Ok(lhs)
+}
+
+fn parse_unary(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    if tokens.get(*pos) == Some(&Token::Not) {
+        *pos += 1;
+        let inner = parse_unary(tokens, pos)?;
+        return Ok(Expr::Not(Box::new(inner)));
+    }
+    parse_atom(tokens, pos)
+}
+
+fn parse_atom(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    if tokens.get(*pos) == Some(&Token::LParen) {
+        *pos += 1;
+        let expr = parse_or(tokens, pos)?;
+        if tokens.get(*pos) != Some(&Token::RParen) {
+            return Err("expected closing ')'".to_string());
+        }
+        *pos += 1;
+        return Ok(expr);
+    }
+
+    let field = match tokens.get(*pos) {
+        Some(Token::Ident(name)) => match name.as_str() {
+            "dir" => Field::Dir,
+            "number" => Field::Number,
+            "timestamp_ns" => Field::TimestampNs,
+            "trigger_offset_ns" => Field::TriggerOffsetNs,
+            "tlp" => Field::Tlp,
+            other => return Err(format!("unknown field: {:?}", other)),
+        },
+        other => return Err(format!("expected a field name, found {:?}", other)),
+    };
+    *pos += 1;
+
+    let op = match tokens.get(*pos) {
+        Some(Token::Op(op)) => *op,
+        other => return Err(format!("expected a comparison operator, found {:?}", other)),
+    };
+    *pos += 1;
+
+    let value = match tokens.get(*pos) {
+        Some(Token::Ident(name)) => Value::Ident(name.clone()),
+        Some(Token::Int(n)) => Value::Int(*n),
+        other => return Err(format!("expected a value, found {:?}", other)),
+    };
+    *pos += 1;
+
+    Ok(Expr::Compare(field, op, value))
+}
@@ -2,7 +2,7 @@
 
 /*
  *  src/lib.rs - Parser library for Agilent PAD files.
- *  Copyright (C) 2023-2024  Forest Crossman <cyrozap@gmail.com>
+ *  Copyright (C) 2023-2025  Forest Crossman <cyrozap@gmail.com>
  *
  *  This program is free software: you can redistribute it and/or modify
  *  it under the terms of the GNU General Public License as published by
@@ -18,12 +18,42 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
 use std::io::prelude::*;
+#[cfg(feature = "std")]
 use std::io::BufReader;
 
-use nom::multi::{count, length_data};
-use nom::number::streaming::{be_u16, be_u32, be_u64, le_u16, le_u32};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+pub mod decode;
+pub mod decoder;
+pub mod error;
+pub mod filter;
+pub mod metadata;
+#[cfg(feature = "std")]
+pub mod pcapng;
+pub mod scramble;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "std")]
+pub mod write;
+
+pub use error::PadError;
+
+use decoder::Decoder;
+
+use nom::number::streaming::{le_u16, le_u32};
 use nom::sequence::tuple;
 use nom::IResult;
 
@@ -35,11 +65,7 @@ fn le_u32_typed(input: &[u8]) -> IResult<&[u8], u32> {
     le_u32(input)
 }
 
-fn parse_string(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    length_data(be_u16)(input)
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Record {
     pub number: u32,
     pub data_len: u32,
@@ -53,7 +79,7 @@ pub struct Record {
 }
 
 impl Record {
-    pub fn from_slice(input: &[u8]) -> Option<Self> {
+    pub fn from_slice(input: &[u8]) -> Result<Self, PadError> {
         match tuple((
             le_u32_typed,
             le_u32,
@@ -68,7 +94,7 @@ impl Record {
             le_u32,
         ))(input)
         {
-            Ok((_, o)) => Some(Self {
+            Ok((_, o)) => Ok(Self {
                 number: o.0,
                 data_len: o.1,
                 count: u32_hi_lo_to_u64(o.2, o.3),
@@ -79,8 +105,23 @@ impl Record {
                 flags: o.8,
                 data_offset: u32_hi_lo_to_u64(o.9, o.10),
             }),
-            Err(e) => panic!("{:?}", e),
+            Err(nom::Err::Incomplete(_)) => Err(PadError::Truncated),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(PadError::Parse {
+                offset: input.len() - e.input.len(),
+            }),
+        }
+    }
+
+    /// Parse this record's trailing metadata region out of its full
+    /// data payload. Returns an empty iterator unless `data_valid` is
+    /// set, since the metadata region only exists past the valid
+    /// payload bytes when the analyzer actually flagged one.
+    pub fn metadata_tlvs<'a>(&self, data: &'a [u8]) -> metadata::TlvIter<'a> {
+        if !self.data_valid {
+            return metadata::TlvIter::new(&[]);
         }
+        let offset = (self.data_valid_count as usize).min(data.len());
+        metadata::TlvIter::new(&data[offset..])
     }
 }
 
@@ -143,105 +184,277 @@ pub struct PadHeader {
     pub start: String,
 }
 
+/// Parses header fields out of a [`Decoder`] one at a time, bubbling up
+/// a field's "need `n` more bytes" error unchanged so callers driving a
+/// growing buffer (like [`PadHeader::from_bufreader`]) know how far to
+/// extend it; a decoder over an already-complete slice (like
+/// [`PadHeader::from_slice`]) can instead treat any such error as
+/// [`PadError::Truncated`].
+fn parse_header_fields(decoder: &mut Decoder) -> Result<PadHeader, usize> {
+    let module_type = decoder.decode_string()?;
+    let port_id = decoder.decode_string()?;
+    let rx_or_tx = decoder.decode_string()?;
+    let description = decoder.decode_string()?;
+    let format_code = decoder.decode_string()?;
+
+    let mut numbers0 = Vec::with_capacity(2);
+    for _ in 0..2 {
+        numbers0.push(decoder.decode_uint_be(4)? as u32);
+    }
+    let trigger_record_number = decoder.decode_uint_be(4)? as u32;
+    let three = decoder.decode_uint_be(4)? as u32;
+    let first_record_number = decoder.decode_uint_be(4)? as u32;
+    let last_record_number = decoder.decode_uint_be(4)? as u32;
+    let record_len = decoder.decode_uint_be(4)? as u32;
+    let timestamp_array_size = decoder.decode_uint_be(4)? as u32;
+
+    let timestamps_ns = TimestampsNs {
+        first: decoder.decode_u64()?,
+        last: decoder.decode_u64()?,
+        stop: decoder.decode_u64()?,
+        trigger: decoder.decode_u64()?,
+    };
+
+    let guid = decoder.decode_string()?;
+    let channel_names = ChannelNames {
+        a: decoder.decode_string()?,
+        b: decoder.decode_string()?,
+    };
+
+    let start_time = CoarseTimestamp::from_slice(&[
+        decoder.decode_uint_be(2)? as u16,
+        decoder.decode_uint_be(2)? as u16,
+        decoder.decode_uint_be(2)? as u16,
+    ]);
+    let stop_time = CoarseTimestamp::from_slice(&[
+        decoder.decode_uint_be(2)? as u16,
+        decoder.decode_uint_be(2)? as u16,
+        decoder.decode_uint_be(2)? as u16,
+    ]);
+
+    let records_offset = decoder.decode_u64()?;
+    let record_data_offset = decoder.decode_u64()?;
+    let start = decoder.decode_string()?;
+
+    Ok(PadHeader {
+        module_type,
+        port_id,
+        rx_or_tx,
+        description,
+        format_code,
+        numbers0,
+        trigger_record_number,
+        three,
+        first_record_number,
+        last_record_number,
+        record_len,
+        timestamp_array_size,
+        timestamps_ns,
+        guid,
+        channel_names,
+        start_time,
+        stop_time,
+        records_offset,
+        record_data_offset,
+        start,
+    })
+}
+
 impl PadHeader {
-    pub fn from_bufreader<R>(pad_reader: &mut BufReader<R>) -> Option<Self>
+    /// Parse a header from a single, already-complete byte slice.
+    pub fn from_slice(input: &[u8]) -> Result<Self, PadError> {
+        let mut decoder = Decoder::new(input);
+        parse_header_fields(&mut decoder).map_err(|_| PadError::Truncated)
+    }
+
+    // The last decode_field! call's store to `offset` is never read
+    // again, since nothing decodes past `start`; keep it anyway so every
+    // field advances the cursor the same way.
+    #[allow(unused_assignments)]
+    #[cfg(feature = "std")]
+    pub fn from_bufreader<R>(pad_reader: &mut BufReader<R>) -> Result<Self, PadError>
     where
         R: Read + Seek,
     {
-        let mut buffer: Vec<u8> = vec![0; 0];
-        let mut expand: usize = 0;
-        loop {
-            buffer.resize_with(buffer.len() + expand, Default::default);
-            pad_reader.read_exact(buffer.as_mut_slice()).unwrap();
-            //println!("bytes read: {}", bytes_read);
-            match tuple((
-                count(parse_string, 5),
-                count(be_u32, 2),
-                be_u32,
-                be_u32,
-                be_u32,
-                be_u32,
-                be_u32,
-                be_u32,
-                be_u64,
-                be_u64,
-                be_u64,
-                be_u64,
-                parse_string,
-                count(parse_string, 2),
-                count(be_u16, 6),
-                be_u64,
-                be_u64,
-                parse_string,
-            ))(buffer.as_slice())
-            {
-                Ok((_, o)) => {
-                    return Some(Self {
-                        module_type: String::from_utf8_lossy(o.0[0]).into(),
-                        port_id: String::from_utf8_lossy(o.0[1]).into(),
-                        rx_or_tx: String::from_utf8_lossy(o.0[2]).into(),
-                        description: String::from_utf8_lossy(o.0[3]).into(),
-                        format_code: String::from_utf8_lossy(o.0[4]).into(),
-                        numbers0: o.1,
-                        trigger_record_number: o.2,
-                        three: o.3,
-                        first_record_number: o.4,
-                        last_record_number: o.5,
-                        record_len: o.6,
-                        timestamp_array_size: o.7,
-                        timestamps_ns: TimestampsNs {
-                            first: o.8,
-                            last: o.9,
-                            stop: o.10,
-                            trigger: o.11,
-                        },
-                        guid: String::from_utf8_lossy(o.12).into(),
-                        channel_names: ChannelNames {
-                            a: String::from_utf8_lossy(o.13[0]).into(),
-                            b: String::from_utf8_lossy(o.13[1]).into(),
-                        },
-                        start_time: CoarseTimestamp::from_slice(&o.14[..3]),
-                        stop_time: CoarseTimestamp::from_slice(&o.14[3..]),
-                        records_offset: o.15,
-                        record_data_offset: o.16,
-                        start: String::from_utf8_lossy(o.17).into(),
-                    })
+        // A single forward pass: `buffer` only ever grows, and `offset`
+        // only ever advances, so no field is ever re-decoded and the
+        // reader is never seeked backward.
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut offset = 0;
+
+        macro_rules! decode_field {
+            ($method:ident $(, $arg:expr)*) => {{
+                loop {
+                    let mut decoder = Decoder::with_offset(&buffer, offset);
+                    match decoder.$method($($arg),*) {
+                        Ok(value) => {
+                            offset = decoder.offset();
+                            break value;
+                        }
+                        Err(needed) => {
+                            let old_len = buffer.len();
+                            buffer.resize(old_len + needed, 0);
+                            pad_reader.read_exact(&mut buffer[old_len..])?;
+                        }
+                    }
                 }
-                Err(nom::Err::Incomplete(nom::Needed::Size(n))) => expand = n.get(),
-                _ => return None,
-            }
-            pad_reader
-                .seek_relative(-(<usize as TryInto<i64>>::try_into(buffer.len()).unwrap()))
-                .unwrap();
+            }};
+        }
+
+        let module_type = decode_field!(decode_string);
+        let port_id = decode_field!(decode_string);
+        let rx_or_tx = decode_field!(decode_string);
+        let description = decode_field!(decode_string);
+        let format_code = decode_field!(decode_string);
+
+        let mut numbers0 = Vec::with_capacity(2);
+        for _ in 0..2 {
+            numbers0.push(decode_field!(decode_uint_be, 4) as u32);
         }
+        let trigger_record_number = decode_field!(decode_uint_be, 4) as u32;
+        let three = decode_field!(decode_uint_be, 4) as u32;
+        let first_record_number = decode_field!(decode_uint_be, 4) as u32;
+        let last_record_number = decode_field!(decode_uint_be, 4) as u32;
+        let record_len = decode_field!(decode_uint_be, 4) as u32;
+        let timestamp_array_size = decode_field!(decode_uint_be, 4) as u32;
+
+        let timestamps_ns = TimestampsNs {
+            first: decode_field!(decode_u64),
+            last: decode_field!(decode_u64),
+            stop: decode_field!(decode_u64),
+            trigger: decode_field!(decode_u64),
+        };
+
+        let guid = decode_field!(decode_string);
+        let channel_names = ChannelNames {
+            a: decode_field!(decode_string),
+            b: decode_field!(decode_string),
+        };
+
+        let start_time = CoarseTimestamp::from_slice(&[
+            decode_field!(decode_uint_be, 2) as u16,
+            decode_field!(decode_uint_be, 2) as u16,
+            decode_field!(decode_uint_be, 2) as u16,
+        ]);
+        let stop_time = CoarseTimestamp::from_slice(&[
+            decode_field!(decode_uint_be, 2) as u16,
+            decode_field!(decode_uint_be, 2) as u16,
+            decode_field!(decode_uint_be, 2) as u16,
+        ]);
+
+        let records_offset = decode_field!(decode_u64);
+        let record_data_offset = decode_field!(decode_u64);
+        let start = decode_field!(decode_string);
+
+        Ok(PadHeader {
+            module_type,
+            port_id,
+            rx_or_tx,
+            description,
+            format_code,
+            numbers0,
+            trigger_record_number,
+            three,
+            first_record_number,
+            last_record_number,
+            record_len,
+            timestamp_array_size,
+            timestamps_ns,
+            guid,
+            channel_names,
+            start_time,
+            stop_time,
+            records_offset,
+            record_data_offset,
+            start,
+        })
     }
 
-    pub fn from_file(pad_file: &mut File) -> Option<Self> {
+    #[cfg(feature = "std")]
+    pub fn from_file(pad_file: &mut File) -> Result<Self, PadError> {
         let mut pad_reader = BufReader::new(pad_file);
 
         Self::from_bufreader(&mut pad_reader)
     }
 }
 
+/// A lazy, streaming view over a PAD file's records: a single cursor
+/// that reads 40-byte record entries on demand from `records_offset` and
+/// can also fetch the associated record data, so callers never have to
+/// juggle a second `File` handle or track read offsets themselves.
+#[cfg(feature = "std")]
 #[derive(Debug)]
-pub struct Records {
+pub struct PadRecords {
     curr: u32,
     last: u32,
-    reader: BufReader<File>,
+    record_reader: BufReader<File>,
+    data_reader: BufReader<File>,
+    curr_data_offset: i64,
 }
 
-impl Records {
-    fn new(first: u32, last: u32, reader: BufReader<File>) -> Self {
+#[cfg(feature = "std")]
+impl PadRecords {
+    fn new(
+        first: u32,
+        last: u32,
+        record_reader: BufReader<File>,
+        data_reader: BufReader<File>,
+    ) -> Self {
         Self {
             curr: first,
             last,
-            reader,
+            record_reader,
+            data_reader,
+            curr_data_offset: 0,
         }
     }
+
+    fn get_data_for_record(
+        &mut self,
+        record: &Record,
+        valid_only: bool,
+    ) -> Result<Vec<u8>, PadError> {
+        self.data_reader.seek_relative(
+            <u64 as TryInto<i64>>::try_into(record.data_offset).unwrap() - self.curr_data_offset,
+        )?;
+
+        let data_read_len = if valid_only && record.data_valid {
+            record.data_valid_count.into()
+        } else {
+            record.data_len.try_into().unwrap()
+        };
+
+        let mut buf: Vec<u8> = vec![0; data_read_len];
+
+        self.data_reader.read_exact(buf.as_mut_slice())?;
+
+        self.curr_data_offset = <u64 as TryInto<i64>>::try_into(record.data_offset).unwrap()
+            + <usize as TryInto<i64>>::try_into(buf.len()).unwrap();
+
+        Ok(buf)
+    }
+
+    /// Fetch only the bytes the analyzer marked valid for `record`.
+    pub fn valid_data_for(&mut self, record: &Record) -> Result<Vec<u8>, PadError> {
+        self.get_data_for_record(record, true)
+    }
+
+    /// Fetch the full data payload for `record`.
+    pub fn data_for(&mut self, record: &Record) -> Result<Vec<u8>, PadError> {
+        self.get_data_for_record(record, false)
+    }
+
+    /// Fetch `record`'s data payload with the PCIe Gen1/Gen2 scrambler
+    /// undone, seeding the descrambler from `record.lfsr`.
+    pub fn descrambled_data_for(&mut self, record: &Record) -> Result<Vec<u8>, PadError> {
+        let data = self.get_data_for_record(record, false)?;
+        Ok(scramble::descramble(&data, record.lfsr))
+    }
 }
 
-impl Iterator for Records {
-    type Item = Record;
+#[cfg(feature = "std")]
+impl Iterator for PadRecords {
+    type Item = Result<Record, PadError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.curr > self.last {
@@ -250,7 +463,9 @@ impl Iterator for Records {
 
         let mut record_buffer = [0; 40];
 
-        self.reader.read_exact(&mut record_buffer).unwrap();
+        if let Err(e) = self.record_reader.read_exact(&mut record_buffer) {
+            return Some(Err(e.into()));
+        }
 
         /* Handle null record */
         if record_buffer.iter().all(|b| *b == 0) {
@@ -258,101 +473,194 @@ impl Iterator for Records {
             return None;
         }
 
-        let record = Record::from_slice(&record_buffer).unwrap();
+        let record = match Record::from_slice(&record_buffer) {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
 
-        assert_eq!(record.number, self.curr, "record number mismatch");
+        if record.number != self.curr {
+            return Some(Err(PadError::UnexpectedRecordNumber {
+                expected: self.curr,
+                found: record.number,
+            }));
+        }
 
         self.curr += 1;
 
-        Some(record)
+        Some(Ok(record))
     }
 }
 
-#[derive(Debug)]
-pub struct RecordReader {
-    data_reader: BufReader<File>,
-    curr_data_offset: i64,
+/// A `Read`-only view over a single record's data, returned by
+/// [`PadFile::record_data_reader`]. Clamped to the record's `data_len`
+/// so a caller can't read past it into the next record's data, and
+/// backed by its own file handle so it doesn't share a cursor with
+/// `PadRecords` or any other `RecordDataReader`.
+#[cfg(feature = "std")]
+pub struct RecordDataReader {
+    file: File,
+    remaining: u64,
 }
 
-impl RecordReader {
-    fn get_data_for_record(&mut self, record: &Record, valid_only: bool) -> Vec<u8> {
-        self.data_reader
-            .seek_relative(
-                <u64 as TryInto<i64>>::try_into(record.data_offset).unwrap()
-                    - self.curr_data_offset,
-            )
-            .unwrap();
-
-        let data_read_len = if valid_only && record.data_valid {
-            record.data_valid_count.into()
-        } else {
-            record.data_len.try_into().unwrap()
-        };
+#[cfg(feature = "std")]
+impl Read for RecordDataReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let max = buf
+            .len()
+            .min(self.remaining.try_into().unwrap_or(usize::MAX));
+        let n = self.file.read(&mut buf[..max])?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
 
-        let mut buf: Vec<u8> = vec![0; data_read_len];
+/// Reads the fixed-size record table in one up-front pass, the same way
+/// `PadRecords::next` walks it incrementally, so that every record's
+/// `data_offset` is known before any record data is fetched by index.
+#[cfg(feature = "std")]
+fn read_record_table(
+    reader: &mut BufReader<File>,
+    first: u32,
+    last: u32,
+) -> Result<Vec<Record>, PadError> {
+    let mut records = Vec::new();
+    let mut curr = first;
 
-        self.data_reader.read_exact(buf.as_mut_slice()).unwrap();
+    while curr <= last {
+        let mut record_buffer = [0; 40];
+        reader.read_exact(&mut record_buffer)?;
 
-        self.curr_data_offset = <u64 as TryInto<i64>>::try_into(record.data_offset).unwrap()
-            + <usize as TryInto<i64>>::try_into(buf.len()).unwrap();
+        if record_buffer.iter().all(|b| *b == 0) {
+            break;
+        }
 
-        buf
-    }
+        let record = Record::from_slice(&record_buffer)?;
+        if record.number != curr {
+            return Err(PadError::UnexpectedRecordNumber {
+                expected: curr,
+                found: record.number,
+            });
+        }
 
-    pub fn get_valid_data_for_record(&mut self, record: &Record) -> Vec<u8> {
-        self.get_data_for_record(record, true)
+        records.push(record);
+        curr += 1;
     }
 
-    pub fn get_all_data_for_record(&mut self, record: &Record) -> Vec<u8> {
-        self.get_data_for_record(record, false)
-    }
+    Ok(records)
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct PadFile {
     pub header: PadHeader,
-    pub records: Records,
-    pub record_reader: RecordReader,
+    pub records: PadRecords,
+    records_index: Vec<Record>,
+    data_file: File,
 }
 
+#[cfg(feature = "std")]
 impl PadFile {
-    pub fn from_filename(filename: &str) -> Result<Self, std::io::Error> {
-        let mut pad_reader = match File::open(filename) {
-            Ok(f) => BufReader::new(f),
-            Err(e) => return Err(e),
-        };
-
-        let mut data_reader = match File::open(filename) {
-            Ok(f) => BufReader::new(f),
-            Err(e) => return Err(e),
-        };
-
-        let header = PadHeader::from_bufreader(&mut pad_reader).unwrap();
+    pub fn from_filename(filename: &str) -> Result<Self, PadError> {
+        let mut pad_reader = BufReader::new(File::open(filename)?);
+        let mut data_reader = BufReader::new(File::open(filename)?);
+        let mut index_reader = BufReader::new(File::open(filename)?);
 
-        assert_eq!(header.record_len, 40, "record length mismatch");
-        assert_eq!(
-            header.timestamp_array_size, 8,
-            "timestamp array size mismatch"
-        );
+        let header = PadHeader::from_bufreader(&mut pad_reader)?;
 
-        pad_reader
-            .seek(std::io::SeekFrom::Start(header.records_offset))
-            .unwrap();
+        if header.record_len != 40 {
+            return Err(PadError::BadRecordLen);
+        }
+        if header.timestamp_array_size != 8 {
+            return Err(PadError::BadTimestampArraySize);
+        }
 
-        data_reader
-            .seek(std::io::SeekFrom::Start(header.record_data_offset))
-            .unwrap();
+        pad_reader.seek(std::io::SeekFrom::Start(header.records_offset))?;
+        data_reader.seek(std::io::SeekFrom::Start(header.record_data_offset))?;
+        index_reader.seek(std::io::SeekFrom::Start(header.records_offset))?;
 
         let first = header.first_record_number;
         let last = header.last_record_number;
 
+        let records_index = read_record_table(&mut index_reader, first, last)?;
+        let data_file = File::open(filename)?;
+
         Ok(Self {
             header,
-            records: Records::new(first, last, pad_reader),
-            record_reader: RecordReader {
-                data_reader,
-                curr_data_offset: 0,
-            },
+            records: PadRecords::new(first, last, pad_reader, data_reader),
+            records_index,
+            data_file,
         })
     }
+
+    /// Looks up an indexed record by its `number` field, rather than
+    /// its position in the table. Records are numbered consecutively
+    /// from `first_record_number`, so this is a direct index rather
+    /// than a search.
+    pub fn get_record(&self, number: u32) -> Result<&Record, PadError> {
+        let index = number
+            .checked_sub(self.header.first_record_number)
+            .ok_or(PadError::IndexOutOfRange(number as usize))? as usize;
+        self.records_index
+            .get(index)
+            .ok_or(PadError::IndexOutOfRange(index))
+    }
+
+    /// Fetches the data for the `index`-th record in the table
+    /// (independent of `self.records`' iteration position), using an
+    /// absolute seek against a freshly cloned file handle. Unlike
+    /// `PadRecords`, repeated calls can be made in any order, from any
+    /// thread, without corrupting a shared cursor.
+    pub fn record_data(&self, index: usize) -> Result<Vec<u8>, PadError> {
+        let record = self
+            .records_index
+            .get(index)
+            .ok_or(PadError::IndexOutOfRange(index))?;
+
+        let mut file = self.data_file.try_clone()?;
+        file.seek(std::io::SeekFrom::Start(
+            self.header.record_data_offset + record.data_offset,
+        ))?;
+
+        let mut buf: Vec<u8> = vec![0; record.data_len.try_into().unwrap()];
+        file.read_exact(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Hands out a bounded reader over the `index`-th record's data,
+    /// clamped to its `data_len` and backed by its own cloned file
+    /// handle, for callers that want to stream a payload (e.g. into a
+    /// decoder) instead of buffering it up front like `record_data`
+    /// does. As with `record_data`, many of these can be in flight on
+    /// different threads at once.
+    pub fn record_data_reader(&self, index: usize) -> Result<RecordDataReader, PadError> {
+        let record = self
+            .records_index
+            .get(index)
+            .ok_or(PadError::IndexOutOfRange(index))?;
+
+        let mut file = self.data_file.try_clone()?;
+        file.seek(std::io::SeekFrom::Start(
+            self.header.record_data_offset + record.data_offset,
+        ))?;
+
+        Ok(RecordDataReader {
+            file,
+            remaining: record.data_len.into(),
+        })
+    }
+
+    /// Extracts every record's data in parallel across a rayon thread
+    /// pool, using the same absolute-offset approach as `record_data`
+    /// so no two threads ever share a seek cursor.
+    #[cfg(feature = "rayon")]
+    pub fn par_records(&self) -> Result<Vec<(Record, Vec<u8>)>, PadError> {
+        use rayon::prelude::*;
+
+        self.records_index
+            .par_iter()
+            .enumerate()
+            .map(|(i, record)| self.record_data(i).map(|data| (record.clone(), data)))
+            .collect()
+    }
 }
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/metadata.rs - Decoder for a record's trailing metadata TLVs.
+ *  Copyright (C) 2025  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! When `Record::data_valid` is set, the bytes past `data_valid_count`
+//! in that record's data are a run of consecutive little-endian
+//! tag/length/value entries rather than more captured payload. This
+//! module decodes that region.
+
+/// Metadata tags this crate currently recognizes. The format hasn't
+/// been reverse-engineered past the tag number itself, so every tag
+/// falls through to `Unknown` for now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataTag {
+    Unknown(u16),
+}
+
+impl From<u16> for MetadataTag {
+    fn from(tag: u16) -> Self {
+        MetadataTag::Unknown(tag)
+    }
+}
+
+/// A single decoded `tag, length, value` entry.
+#[derive(Debug, Clone, Copy)]
+pub struct Tlv<'a> {
+    pub tag: u16,
+    pub value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    pub fn metadata_tag(&self) -> MetadataTag {
+        MetadataTag::from(self.tag)
+    }
+}
+
+/// Walks a record's metadata region as consecutive `Tlv`s, stopping at
+/// the first truncated or trailing-garbage entry.
+pub struct TlvIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TlvIter<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for TlvIter<'a> {
+    type Item = Tlv<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < 4 {
+            return None;
+        }
+
+        let tag = u16::from_le_bytes(self.data[0..2].try_into().unwrap());
+        let len = u16::from_le_bytes(self.data[2..4].try_into().unwrap()) as usize;
+        if self.data.len() < 4 + len {
+            return None;
+        }
+
+        let value = &self.data[4..4 + len];
+        self.data = &self.data[4 + len..];
+        Some(Tlv { tag, value })
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/pcapng.rs - Typed encoders for the PCAP-NG blocks this crate emits.
+ *  Copyright (C) 2025  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Self-contained PCAP-NG block types: each owns its options and knows
+//! its own length, so callers just build a block and write it rather
+//! than hand-assembling a `Vec<u8>` with inline length/padding math.
+
+use std::io::{self, Write};
+
+const BLOCK_TYPE_SECTION_HEADER: u32 = 0x0a0d0d0a;
+const BLOCK_TYPE_INTERFACE_DESCRIPTION: u32 = 0x00000001;
+const BLOCK_TYPE_ENHANCED_PACKET: u32 = 0x00000006;
+
+/// A PCAP-NG block that can serialize itself, including its trailing
+/// Block Total Length.
+pub trait WritableBlock {
+    /// The number of bytes [`WritableBlock::write_to`] will write.
+    fn len_written(&self) -> usize;
+
+    /// Write this block, including its trailing Block Total Length, to `w`.
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize>;
+}
+
+fn pad_len(len: usize) -> usize {
+    (4 - (len % 4)) % 4
+}
+
+/// A single 4-byte-aligned PCAP-NG option.
+struct BlockOption {
+    code: u16,
+    value: Vec<u8>,
+}
+
+impl BlockOption {
+    fn len_written(&self) -> usize {
+        4 + self.value.len() + pad_len(self.value.len())
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        w.write_all(&self.code.to_le_bytes())?;
+        w.write_all(
+            &<usize as TryInto<u16>>::try_into(self.value.len())
+                .unwrap()
+                .to_le_bytes(),
+        )?;
+        w.write_all(&self.value)?;
+        w.write_all(&vec![0; pad_len(self.value.len())])?;
+        Ok(self.len_written())
+    }
+}
+
+fn options_len(options: &[BlockOption]) -> usize {
+    if options.is_empty() {
+        0
+    } else {
+        options.iter().map(BlockOption::len_written).sum::<usize>() + 4 // opt_endofopt
+    }
+}
+
+fn write_options<W: Write>(options: &[BlockOption], w: &mut W) -> io::Result<usize> {
+    if options.is_empty() {
+        return Ok(0);
+    }
+
+    let mut n = 0;
+    for option in options {
+        n += option.write_to(w)?;
+    }
+    w.write_all(&0_u16.to_le_bytes())?;
+    w.write_all(&0_u16.to_le_bytes())?;
+    n += 4;
+    Ok(n)
+}
+
+/// The PCAP-NG Section Header Block. This crate never sets any
+/// section-level options.
+pub struct SectionHeaderBlock;
+
+impl WritableBlock for SectionHeaderBlock {
+    fn len_written(&self) -> usize {
+        4 + 4 + 4 + 2 + 2 + 8 + 4
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let len: u32 = <usize as TryInto<u32>>::try_into(self.len_written()).unwrap();
+
+        w.write_all(&BLOCK_TYPE_SECTION_HEADER.to_le_bytes())?;
+        w.write_all(&len.to_le_bytes())?;
+        w.write_all(&0x1a2b3c4d_u32.to_le_bytes())?;
+        w.write_all(&1_u16.to_le_bytes())?;
+        w.write_all(&0_u16.to_le_bytes())?;
+        w.write_all(&(-1_i64).to_le_bytes())?;
+        w.write_all(&len.to_le_bytes())?;
+        Ok(self.len_written())
+    }
+}
+
+/// The PCAP-NG Interface Description Block describing a single capture
+/// interface (here, one PAD module port).
+pub struct InterfaceDescriptionBlock {
+    pub link_type: u16,
+    pub if_name: String,
+    pub if_hardware: String,
+    /// `if_tsresol` option value: resolution is `10^-tsresol` seconds.
+    pub tsresol: u8,
+}
+
+impl InterfaceDescriptionBlock {
+    fn options(&self) -> Vec<BlockOption> {
+        vec![
+            BlockOption {
+                code: 2, // if_name
+                value: self.if_name.clone().into_bytes(),
+            },
+            BlockOption {
+                code: 9, // if_tsresol
+                value: vec![self.tsresol],
+            },
+            BlockOption {
+                code: 15, // if_hardware
+                value: self.if_hardware.clone().into_bytes(),
+            },
+        ]
+    }
+}
+
+impl WritableBlock for InterfaceDescriptionBlock {
+    fn len_written(&self) -> usize {
+        4 + 4 + 2 + 2 + 4 + options_len(&self.options()) + 4
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let options = self.options();
+        let len: u32 = <usize as TryInto<u32>>::try_into(self.len_written()).unwrap();
+
+        w.write_all(&BLOCK_TYPE_INTERFACE_DESCRIPTION.to_le_bytes())?;
+        w.write_all(&len.to_le_bytes())?;
+        w.write_all(&self.link_type.to_le_bytes())?;
+        w.write_all(&0_u16.to_le_bytes())?;
+        w.write_all(&0_u32.to_le_bytes())?;
+        write_options(&options, w)?;
+        w.write_all(&len.to_le_bytes())?;
+        Ok(self.len_written())
+    }
+}
+
+/// The PCAP-NG Enhanced Packet Block: one captured record, with an
+/// optional human-readable comment.
+pub struct EnhancedPacketBlock {
+    pub interface_id: u32,
+    pub timestamp_ns: u64,
+    pub data: Vec<u8>,
+    pub comment: Option<String>,
+}
+
+impl EnhancedPacketBlock {
+    fn options(&self) -> Vec<BlockOption> {
+        match &self.comment {
+            Some(comment) if !comment.is_empty() => vec![BlockOption {
+                code: 1, // opt_comment
+                value: comment.clone().into_bytes(),
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl WritableBlock for EnhancedPacketBlock {
+    fn len_written(&self) -> usize {
+        let data_len = self.data.len();
+        4 + 4 + 4 + 4 + 4 + 4 + 4 + data_len + pad_len(data_len) + options_len(&self.options()) + 4
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let options = self.options();
+        let len: u32 = <usize as TryInto<u32>>::try_into(self.len_written()).unwrap();
+        let data_len: u32 = <usize as TryInto<u32>>::try_into(self.data.len()).unwrap();
+
+        w.write_all(&BLOCK_TYPE_ENHANCED_PACKET.to_le_bytes())?;
+        w.write_all(&len.to_le_bytes())?;
+        w.write_all(&self.interface_id.to_le_bytes())?;
+        w.write_all(
+            &<u64 as TryInto<u32>>::try_into(self.timestamp_ns.checked_shr(32).unwrap())
+                .unwrap()
+                .to_le_bytes(),
+        )?;
+        w.write_all(
+            &<u64 as TryInto<u32>>::try_into(self.timestamp_ns & ((1 << 32) - 1))
+                .unwrap()
+                .to_le_bytes(),
+        )?;
+        w.write_all(&data_len.to_le_bytes())?;
+        w.write_all(&data_len.to_le_bytes())?;
+        w.write_all(&self.data)?;
+        w.write_all(&vec![0; pad_len(self.data.len())])?;
+        write_options(&options, w)?;
+        w.write_all(&len.to_le_bytes())?;
+        Ok(self.len_written())
+    }
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/scramble.rs - PCIe Gen1/Gen2 data scrambler/descrambler.
+ *  Copyright (C) 2024  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The PCIe Gen1/Gen2 data scrambler, generator polynomial
+//! `G(x) = x^16 + x^5 + x^4 + x^3 + 1`. The analyzer captures the raw,
+//! scrambled 8b/10b payload and stores the scrambler's 16-bit LFSR state
+//! for the start of each record in `Record::lfsr`; this module lets
+//! callers recover the unscrambled symbol stream from that seed.
+//!
+//! Scrambling is XOR-based and therefore self-inverse: the same
+//! [`descramble`] routine both scrambles and descrambles a byte stream.
+
+use alloc::vec::Vec;
+
+use crate::decode::{COM, SKP};
+
+/// The 16-bit scrambler LFSR, seeded from a record's `lfsr` field.
+struct Lfsr(u16);
+
+impl Lfsr {
+    fn new(seed: u16) -> Self {
+        Self(seed)
+    }
+
+    fn reset(&mut self) {
+        self.0 = 0xffff;
+    }
+
+    /// Derive the next 8-bit scrambling value from the register's
+    /// current state, then advance the register 8 bit-positions.
+    fn next_byte(&mut self) -> u8 {
+        let byte = (self.0 & 0xff) as u8;
+        for _ in 0..8 {
+            let feedback = ((self.0 >> 15) ^ (self.0 >> 4) ^ (self.0 >> 3) ^ (self.0 >> 2)) & 1;
+            self.0 = (self.0 << 1) | feedback;
+        }
+        byte
+    }
+}
+
+/// Scramble or descramble `data`, seeding the LFSR from `seed` (a
+/// record's `lfsr` field). `COM` resets the LFSR to `0xffff` as it does
+/// on the wire, and `COM`/`SKP` symbols are passed through unscrambled.
+/// Since scrambling is XOR-based, this same function performs both
+/// directions.
+pub fn descramble(data: &[u8], seed: u16) -> Vec<u8> {
+    let mut lfsr = Lfsr::new(seed);
+    let mut out = Vec::with_capacity(data.len());
+
+    for &byte in data {
+        match byte {
+            COM => {
+                lfsr.reset();
+                out.push(byte);
+            }
+            SKP => out.push(byte),
+            _ => out.push(byte ^ lfsr.next_byte()),
+        }
+    }
+
+    out
+}
+
+/// Run `data` through the descrambler starting from `seed` and return
+/// the LFSR's state once every byte has been consumed, without
+/// allocating the descrambled output.
+fn lfsr_state_after(data: &[u8], seed: u16) -> u16 {
+    let mut lfsr = Lfsr::new(seed);
+
+    for &byte in data {
+        match byte {
+            COM => lfsr.reset(),
+            SKP => {}
+            _ => {
+                lfsr.next_byte();
+            }
+        }
+    }
+
+    lfsr.0
+}
+
+/// Confirm a record's `seed` actually matches its captured bytes.
+///
+/// Descrambling is self-inverse for *any* seed, so comparing
+/// `descramble(descramble(data, seed), seed)` against `data` is a
+/// tautology and can never catch a wrong seed. Instead, since the
+/// scrambler LFSR runs continuously across records (barring a `COM`
+/// reset), the state it's left in after descrambling `data` should
+/// equal `next_seed`, the following record's captured `lfsr` field.
+/// Returns `true` when `next_seed` is `None` (there's no following
+/// record to compare against, e.g. the last record in a capture).
+pub fn self_check(data: &[u8], seed: u16, next_seed: Option<u16>) -> bool {
+    match next_seed {
+        Some(next_seed) => lfsr_state_after(data, seed) == next_seed,
+        None => true,
+    }
+}
@@ -0,0 +1,326 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/*
+ *  src/write.rs - Serializers for PAD's header and record formats.
+ *  Copyright (C) 2025  Forest Crossman <cyrozap@gmail.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The write-side mirror of the parsing in `lib.rs`: each type that
+//! `PadHeader::from_bufreader`/`Record::from_slice` can decode also
+//! knows how to serialize itself back to the same bytes, via
+//! [`ToWriter`]. [`PadWriter`] builds on that to lay out a whole PAD
+//! file (header, record table, record data) and patch the offset
+//! fields to match.
+
+use std::io::{self, Write};
+
+use crate::{ChannelNames, CoarseTimestamp, PadError, PadHeader, Record, TimestampsNs};
+
+/// A PAD value that can serialize itself back to the bytes
+/// `Record::from_slice`/`PadHeader::from_bufreader` would parse it from.
+pub trait ToWriter {
+    /// The number of bytes [`ToWriter::write_to`] will write.
+    fn len_written(&self) -> usize;
+
+    /// Write this value's bytes to `w`.
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize>;
+}
+
+fn write_length_prefixed_be16<W: Write>(w: &mut W, s: &str) -> io::Result<usize> {
+    let bytes = s.as_bytes();
+    let len: u16 = bytes.len().try_into().expect("header string too long");
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(bytes)?;
+    Ok(2 + bytes.len())
+}
+
+fn u64_to_hi_lo_u32(v: u64) -> (u32, u32) {
+    ((v >> u32::BITS) as u32, v as u32)
+}
+
+impl ToWriter for CoarseTimestamp {
+    fn len_written(&self) -> usize {
+        6
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        w.write_all(&self.hour.to_be_bytes())?;
+        w.write_all(&self.minute.to_be_bytes())?;
+        w.write_all(&self.millisec.to_be_bytes())?;
+        Ok(self.len_written())
+    }
+}
+
+impl ToWriter for TimestampsNs {
+    fn len_written(&self) -> usize {
+        32
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        w.write_all(&self.first.to_be_bytes())?;
+        w.write_all(&self.last.to_be_bytes())?;
+        w.write_all(&self.stop.to_be_bytes())?;
+        w.write_all(&self.trigger.to_be_bytes())?;
+        Ok(self.len_written())
+    }
+}
+
+impl ToWriter for ChannelNames {
+    fn len_written(&self) -> usize {
+        4 + self.a.len() + self.b.len()
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        write_length_prefixed_be16(w, &self.a)?;
+        write_length_prefixed_be16(w, &self.b)?;
+        Ok(self.len_written())
+    }
+}
+
+impl ToWriter for PadHeader {
+    fn len_written(&self) -> usize {
+        10 // 5 length-prefixed strings' u16 length fields
+            + self.module_type.len()
+            + self.port_id.len()
+            + self.rx_or_tx.len()
+            + self.description.len()
+            + self.format_code.len()
+            + 4 * (self.numbers0.len() + 6) // numbers0, trigger_record_number, three, first/last_record_number, record_len, timestamp_array_size
+            + self.timestamps_ns.len_written()
+            + 2
+            + self.guid.len()
+            + self.channel_names.len_written()
+            + self.start_time.len_written()
+            + self.stop_time.len_written()
+            + 8
+            + 8
+            + 2
+            + self.start.len()
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        write_length_prefixed_be16(w, &self.module_type)?;
+        write_length_prefixed_be16(w, &self.port_id)?;
+        write_length_prefixed_be16(w, &self.rx_or_tx)?;
+        write_length_prefixed_be16(w, &self.description)?;
+        write_length_prefixed_be16(w, &self.format_code)?;
+
+        for n in &self.numbers0 {
+            w.write_all(&n.to_be_bytes())?;
+        }
+        w.write_all(&self.trigger_record_number.to_be_bytes())?;
+        w.write_all(&self.three.to_be_bytes())?;
+        w.write_all(&self.first_record_number.to_be_bytes())?;
+        w.write_all(&self.last_record_number.to_be_bytes())?;
+        w.write_all(&self.record_len.to_be_bytes())?;
+        w.write_all(&self.timestamp_array_size.to_be_bytes())?;
+
+        self.timestamps_ns.write_to(w)?;
+
+        write_length_prefixed_be16(w, &self.guid)?;
+        self.channel_names.write_to(w)?;
+
+        self.start_time.write_to(w)?;
+        self.stop_time.write_to(w)?;
+
+        w.write_all(&self.records_offset.to_be_bytes())?;
+        w.write_all(&self.record_data_offset.to_be_bytes())?;
+
+        write_length_prefixed_be16(w, &self.start)?;
+
+        Ok(self.len_written())
+    }
+}
+
+impl ToWriter for Record {
+    fn len_written(&self) -> usize {
+        40
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let (count_hi, count_lo) = u64_to_hi_lo_u32(self.count);
+        let (timestamp_ns_hi, timestamp_ns_lo) = u64_to_hi_lo_u32(self.timestamp_ns);
+        let (data_offset_hi, data_offset_lo) = u64_to_hi_lo_u32(self.data_offset);
+        let metadata_info: u16 = if self.data_valid { 0x8000 } else { 0 } | self.data_valid_count;
+
+        w.write_all(&self.number.to_le_bytes())?;
+        w.write_all(&self.data_len.to_le_bytes())?;
+        w.write_all(&count_hi.to_le_bytes())?;
+        w.write_all(&count_lo.to_le_bytes())?;
+        w.write_all(&timestamp_ns_hi.to_le_bytes())?;
+        w.write_all(&timestamp_ns_lo.to_le_bytes())?;
+        w.write_all(&self.lfsr.to_le_bytes())?;
+        w.write_all(&metadata_info.to_le_bytes())?;
+        w.write_all(&self.flags.to_le_bytes())?;
+        w.write_all(&data_offset_hi.to_le_bytes())?;
+        w.write_all(&data_offset_lo.to_le_bytes())?;
+
+        Ok(self.len_written())
+    }
+}
+
+/// Lays out a PAD file's header, record table, and record data regions
+/// in order, patching `header.records_offset`/`record_data_offset` and
+/// each record's `data_offset`/`data_len` to match, so a caller only
+/// has to supply a header and the `(Record, Vec<u8>)` pairs to emit a
+/// file `PadFile::from_filename` can read back.
+pub struct PadWriter {
+    pub header: PadHeader,
+    pub records: Vec<(Record, Vec<u8>)>,
+}
+
+impl PadWriter {
+    pub fn new(header: PadHeader, records: Vec<(Record, Vec<u8>)>) -> Self {
+        Self { header, records }
+    }
+
+    pub fn write_to<W: Write>(&mut self, w: &mut W) -> Result<(), PadError> {
+        self.header.records_offset = self.header.len_written() as u64;
+        self.header.record_data_offset =
+            self.header.records_offset + (self.records.len() * 40) as u64;
+
+        let mut data_offset = 0u64;
+        for (record, data) in self.records.iter_mut() {
+            record.data_offset = data_offset;
+            record.data_len = data.len().try_into().expect("record data too long");
+            data_offset += data.len() as u64;
+        }
+
+        self.header.write_to(w)?;
+        for (record, _) in &self.records {
+            record.write_to(w)?;
+        }
+        for (_, data) in &self.records {
+            w.write_all(data)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_header() -> PadHeader {
+        PadHeader {
+            module_type: "U4301A".to_string(),
+            port_id: "1".to_string(),
+            rx_or_tx: "RX".to_string(),
+            description: "test capture".to_string(),
+            format_code: "1".to_string(),
+            numbers0: vec![0, 1],
+            trigger_record_number: 7,
+            three: 3,
+            first_record_number: 1,
+            last_record_number: 3,
+            record_len: 40,
+            timestamp_array_size: 8,
+            timestamps_ns: TimestampsNs {
+                first: 100,
+                last: 400,
+                stop: 500,
+                trigger: 250,
+            },
+            guid: "{00000000-0000-0000-0000-000000000000}".to_string(),
+            channel_names: ChannelNames {
+                a: "A".to_string(),
+                b: "B".to_string(),
+            },
+            start_time: CoarseTimestamp {
+                hour: 12,
+                minute: 34,
+                millisec: 56,
+            },
+            stop_time: CoarseTimestamp {
+                hour: 12,
+                minute: 35,
+                millisec: 0,
+            },
+            records_offset: 0,
+            record_data_offset: 0,
+            start: "started".to_string(),
+        }
+    }
+
+    fn sample_record(number: u32) -> Record {
+        Record {
+            number,
+            data_len: 0,
+            count: 1,
+            timestamp_ns: 1_000_000 * number as u64,
+            lfsr: 0xbeef,
+            data_valid: true,
+            data_valid_count: 4,
+            flags: 0x1000_0000,
+            data_offset: 0,
+        }
+    }
+
+    /// Writing a header/record table and reading it back with
+    /// `PadHeader::from_slice`/`Record::from_slice` must reproduce the
+    /// same field values, and each record's data must be found at
+    /// `record_data_offset + data_offset`, the origin `PadFile::record_data`
+    /// and `PadRecords` read it from. The data is sliced out of the
+    /// written buffer here; `PadFile::record_data` itself is not called.
+    #[test]
+    fn header_and_records_round_trip() {
+        let records = vec![
+            (sample_record(1), vec![0xde, 0xad, 0xbe, 0xef]),
+            (sample_record(2), vec![0x01, 0x02, 0x03]),
+            (sample_record(3), vec![]),
+        ];
+
+        let mut writer = PadWriter::new(sample_header(), records.clone());
+
+        let mut buf = Vec::new();
+        writer.write_to(&mut buf).unwrap();
+
+        let header = PadHeader::from_slice(&buf).unwrap();
+        assert_eq!(header.module_type, "U4301A");
+        assert_eq!(header.numbers0, vec![0, 1]);
+        assert_eq!(header.trigger_record_number, 7);
+        assert_eq!(header.timestamps_ns.first, 100);
+        assert_eq!(header.timestamps_ns.trigger, 250);
+        assert_eq!(header.channel_names.a, "A");
+        assert_eq!(header.channel_names.b, "B");
+        assert_eq!(header.records_offset, writer.header.records_offset);
+        assert_eq!(
+            header.record_data_offset,
+            writer.header.record_data_offset
+        );
+        assert_eq!(header.start, "started");
+
+        let records_offset = header.records_offset as usize;
+        let record_data_offset = header.record_data_offset as usize;
+        for (i, (expected_record, expected_data)) in records.iter().enumerate() {
+            let record_bytes = &buf[records_offset + i * 40..records_offset + (i + 1) * 40];
+            let record = Record::from_slice(record_bytes).unwrap();
+            assert_eq!(record.number, expected_record.number);
+            assert_eq!(record.count, expected_record.count);
+            assert_eq!(record.timestamp_ns, expected_record.timestamp_ns);
+            assert_eq!(record.lfsr, expected_record.lfsr);
+            assert_eq!(record.data_valid, expected_record.data_valid);
+            assert_eq!(record.data_valid_count, expected_record.data_valid_count);
+            assert_eq!(record.flags, expected_record.flags);
+            assert_eq!(record.data_len as usize, expected_data.len());
+
+            let data_start = record_data_offset + record.data_offset as usize;
+            let data = &buf[data_start..data_start + record.data_len as usize];
+            assert_eq!(data, expected_data.as_slice());
+        }
+    }
+}